@@ -1,5 +1,6 @@
 extern crate time;
 
+use std::collections::HashMap;
 use cgmath::{Rad, Point2, Vector2};
 use gl_init;
 use gfx;
@@ -7,50 +8,100 @@ use gfx::DeviceHelper;
 use sys;
 use world;
 
-pub type EventReceiver = (
-	Receiver<sys::control::Event>,
-	Receiver<sys::bullet::Event>
-);
+/// A logical input, decoupled from the physical key that triggers it.
+#[deriving(PartialEq, Eq, Hash, Clone)]
+pub enum Action {
+	Thrust,
+	TurnLeft,
+	TurnRight,
+	Shoot,
+	Boost,
+}
+
+/// Remappable key-to-action table.
+pub type Bindings = HashMap<gl_init::VirtualKeyCode, Action>;
+
+/// A second press of the same action within this window reads as a double-tap.
+static DOUBLE_TAP: u64 = 250_000_000;
 
+/// Turns physical input into the per-frame `net::Input` bitset the session
+/// replays, plus the out-of-band camera events that never touch the
+/// simulation.
 pub struct EventSender {
-	control: Sender<sys::control::Event>,
-	bullet: Sender<sys::bullet::Event>,
+	camera: Sender<sys::camera::Event>,
+	bindings: Bindings,
+	last_press: HashMap<Action, u64>,
+	held: sys::net::Input,
 }
 
 impl EventSender {
-	pub fn new() -> (EventSender, EventReceiver) {
-		let (sc, rc) = channel();
-		let (sb, rb) = channel();
-		(EventSender {
-			control: sc,
-			bullet: sb,
-		}, (rc, rb))
+	/// The stock layout: A thrusts, arrows turn, S shoots. Tapping thrust
+	/// twice quickly boosts.
+	pub fn default_bindings() -> Bindings {
+		let mut b = HashMap::new();
+		b.insert(gl_init::A, Thrust);
+		b.insert(gl_init::Left, TurnLeft);
+		b.insert(gl_init::Right, TurnRight);
+		b.insert(gl_init::S, Shoot);
+		b
+	}
+
+	pub fn new(bindings: Bindings, camera: Sender<sys::camera::Event>) -> EventSender {
+		EventSender {
+			camera: camera,
+			bindings: bindings,
+			last_press: HashMap::new(),
+			held: 0,
+		}
 	}
 
-	pub fn process(&self, event: gl_init::Event) {
-		use sys::control::{EvThrust, EvTurn};
-		use sys::bullet::{EvShoot};
+	/// The current input bitset, to be passed to `Game::render`, which applies
+	/// it to every fixed tick of that call.
+	pub fn input(&self) -> sys::net::Input {
+		self.held
+	}
+
+	pub fn process(&mut self, event: gl_init::Event) {
+		use sys::camera::{EvZoom};
 		match event {
-			gl_init::KeyboardInput(state, _, Some(gl_init::A), _) =>
-				self.control.send(EvThrust(match state {
-					gl_init::Pressed => 1.0,
-					gl_init::Released => 0.0,
-				})),
-			gl_init::KeyboardInput(gl_init::Pressed, _, Some(gl_init::Left), _) =>
-				self.control.send(EvTurn(-1.0)),
-			gl_init::KeyboardInput(gl_init::Pressed, _, Some(gl_init::Right), _) =>
-				self.control.send(EvTurn(1.0)),
-			gl_init::KeyboardInput(gl_init::Released, _, Some(k), _)
-				if k == gl_init::Left || k == gl_init::Right =>
-				self.control.send(EvTurn(0.0)),
-			gl_init::KeyboardInput(state, _, Some(gl_init::S), _) =>
-				self.bullet.send(EvShoot(match state {
-					gl_init::Pressed => true,
-					gl_init::Released => false,
-				})),
+			gl_init::KeyboardInput(state, _, Some(key), _) =>
+				match self.bindings.find(&key) {
+					Some(&action) => self.dispatch(action, state),
+					None => (),
+				},
+			gl_init::MouseWheel(delta) =>
+				self.camera.send(EvZoom(delta as f32)),
 			_ => (),
 		}
 	}
+
+	fn dispatch(&mut self, action: Action, state: gl_init::ElementState) {
+		let pressed = state == gl_init::Pressed;
+		let bit = match action {
+			Thrust => sys::net::THRUST,
+			TurnLeft => sys::net::TURN_LEFT,
+			TurnRight => sys::net::TURN_RIGHT,
+			Shoot => sys::net::SHOOT,
+			Boost => sys::net::BOOST,
+		};
+		if pressed {
+			self.held |= bit;
+			// a quick second tap of thrust promotes it to a boost
+			if action == Thrust {
+				let now = time::precise_time_ns();
+				if self.last_press.find(&Thrust).map_or(false, |&t| now - t < DOUBLE_TAP) {
+					self.held |= sys::net::BOOST;
+				}
+				self.last_press.insert(Thrust, now);
+			}
+		} else {
+			self.held &= !bit;
+			// releasing thrust also drops any boost it escalated to
+			if action == Thrust {
+				self.held &= !sys::net::BOOST;
+			}
+		}
+	}
 }
 
 #[vertex_format]
@@ -71,7 +122,12 @@ impl Vertex {
 
 pub struct Game {
 	world: world::World,
+	net: sys::net::Session,
+	/// Hits reported by the collision system, awaiting a health system.
+	hits: Receiver<sys::collision::Event>,
 	last_time: u64,
+	/// Unconsumed wall-clock time waiting to be spent in fixed ticks.
+	accumulator: world::Delta,
 }
 
 impl Game {
@@ -108,7 +164,8 @@ impl Game {
 	}
 
 	fn create_ship<T, D: gfx::Device<T>>(device: &mut D, data: &mut world::Components,
-				   draw: &mut sys::draw::System, program: world::Program)
+				   draw: &mut sys::draw::System, program: world::Program,
+				   pos: Point2<f32>, directive: Option<world::Directive>)
 				   -> world::Entity {
 		let mesh = device.create_mesh(vec![
 			Vertex::new(-0.3, -0.5, 0x20C02000),
@@ -118,7 +175,7 @@ impl Game {
 		let slice = mesh.get_slice();
 		let mut state = gfx::DrawState::new();
 		state.primitive.method = gfx::state::Fill(gfx::state::CullNothing);
-		data.add()
+		let mut builder = data.add()
 			.draw(world::Drawable {
 				program: program,
 				mesh_id: draw.meshes.add(mesh),
@@ -126,7 +183,7 @@ impl Game {
 				slice: slice,
 			})
 			.space(world::Spatial {
-				pos: Point2::new(0.0, 0.0),
+				pos: pos,
 				orient: Rad{ s: 0.0 },
 				scale: 1.0,
 			})
@@ -137,12 +194,35 @@ impl Game {
 			.control(world::Control {
 				thrust_speed: 4.0,
 				turn_speed: -90.0,
+				fire_cooldown: 0.0,
+				thrust: 0.0,
+				turn: 0.0,
 			})
-			.entity
+			.collide(world::Collidable {
+				radius: 0.5,
+				group: sys::collision::SHIP,
+				mask: sys::collision::BULLET,
+			});
+		// non-player ships carry a steering directive for the AI system
+		match directive {
+			Some(d) => { builder = builder.directive(d); },
+			None => (),
+		}
+		builder.entity
 	}
 
-	pub fn new<T, D: gfx::Device<T>>(frame: gfx::Frame,
-			   (ev_control, ev_bullet): EventReceiver, device: &mut D) -> Game {
+	pub fn new<T, D: gfx::Device<T>>(frame: gfx::Frame, device: &mut D)
+			   -> (Game, EventSender) {
+		let aspect = frame.width as f32 / frame.height as f32;
+		// player input flows as a bitset through the net session, which is the
+		// only path from input to the control/bullet channels -- both live and on
+		// replay; the AI system writes to the control channel directly
+		let (sc, ev_control) = channel();
+		let ai_control = sc.clone();
+		let (sb, ev_bullet) = channel();
+		let (sm, ev_camera) = channel();
+		let (to_peer, from_peer) = channel();
+		let (hit_sender, hits) = channel();
 		let mut w = world::World::new();
 		// prepare systems
 		let program = Game::create_program(device);
@@ -162,27 +242,71 @@ impl Game {
 				slice: slice,
 			}
 		};
-		let ship = Game::create_ship(device, &mut w.data, &mut draw_system, program);
-		let (space_id, inertia_id) = (ship.space.unwrap(), ship.inertia.unwrap());
+		let ship = Game::create_ship(device, &mut w.data, &mut draw_system,
+			program.clone(), Point2::new(0.0, 0.0), None);
+		let (space_id, inertia_id, control_id) =
+			(ship.space.unwrap(), ship.inertia.unwrap(), ship.control.unwrap());
+		// the peer's ship, driven entirely by the inputs the session replays
+		let peer = Game::create_ship(device, &mut w.data, &mut draw_system,
+			program.clone(), Point2::new(-3.0, 0.0), None);
+		let peer_control = peer.control.unwrap();
+		// a single AI ship that hunts the player
+		let enemy = Game::create_ship(device, &mut w.data, &mut draw_system,
+			program, Point2::new(3.0, 3.0), Some(world::Pursue(ship.clone())));
+		// the camera tracks the ship and starts at the default zoom
+		let camera = w.data.add()
+			.camera(world::Camera {
+				pos: Point2::new(0.0, 0.0),
+				zoom: 0.1,
+			})
+			.entity;
+		let camera_id = camera.camera.unwrap();
 		// populate world and return
 		w.entities.push(ship);
+		w.entities.push(peer);
+		w.entities.push(enemy);
+		w.entities.push(camera);
 		w.systems.push_all_move(vec![
+			box sys::camera::System::new(ev_camera,
+				camera_id, Some(space_id), aspect) as Box<world::System + Send>,
 			box draw_system as Box<world::System + Send>,
+			box sys::ai::System::new(ai_control),
 			box sys::inertia::System,
 			box sys::control::System::new(ev_control),
 			box sys::bullet::System::new(ev_bullet,
-				space_id, inertia_id, bullet_draw),
+				space_id, inertia_id, control_id, bullet_draw),
+			box sys::collision::System::new(hit_sender),
 		]);
-		Game {
+		// loop the session's packets back to itself so the rollback path stays
+		// live in single-player until a real peer socket is plugged in
+		let net = sys::net::Session::new(to_peer, from_peer, sc, sb, control_id, peer_control);
+		let game = Game {
 			world: w,
+			net: net,
+			hits: hits,
 			last_time: time::precise_time_ns(),
-		}
+			accumulator: 0.0,
+		};
+		(game, EventSender::new(EventSender::default_bindings(), sm))
 	}
 
-	pub fn render(&mut self, list: &mut gfx::DrawList) {
+	pub fn render(&mut self, list: &mut gfx::DrawList, local: sys::net::Input) {
+		// Real time only decides how many fixed ticks to run; the simulation
+		// itself never sees wall-clock delta, so it stays deterministic and
+		// replayable by the session's rollback. See sys::net::TIMESTEP.
 		let new_time = time::precise_time_ns();
-		let delta = (new_time - self.last_time) as f32 / 1e9;
+		self.accumulator += (new_time - self.last_time) as f32 / 1e9;
 		self.last_time = new_time;
-		self.world.update(&mut (delta, list));
+		while self.accumulator >= sys::net::TIMESTEP {
+			self.net.advance(&mut self.world, local, list);
+			self.accumulator -= sys::net::TIMESTEP;
+		}
+		// drain reported hits until a health system consumes them
+		loop {
+			match self.hits.try_recv() {
+				Ok(_) => (),
+				Err(_) => break,
+			}
+		}
 	}
 }
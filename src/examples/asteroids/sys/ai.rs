@@ -0,0 +1,97 @@
+use cgmath::{Rad, Point, Vector, EuclideanVector, Vector2};
+use scenegraph::ces;
+use sys;
+use world;
+
+/// Cosine of the half-angle within which the ship considers itself aimed at
+/// its goal and may apply thrust.
+static ALIGN: f32 = 0.9;
+/// A `Pursue` ship that gets this close flips to `Flee` instead.
+static FLEE_RADIUS: f32 = 2.0;
+/// Largest heading change `Wander` applies per frame.
+static WANDER_JITTER: f32 = 0.2;
+
+pub struct System {
+	control: Sender<sys::control::Event>,
+}
+
+impl System {
+	pub fn new(control: Sender<sys::control::Event>) -> System {
+		System {
+			control: control,
+		}
+	}
+
+	/// Turn towards `desired` and thrust once roughly aligned, driving only the
+	/// ship named by `cid`. A zero-length goal (we are already on the target)
+	/// leaves the ship coasting.
+	fn steer(&self, cid: ces::Id<world::Control>, dir: Vector2<f32>, desired: Vector2<f32>) {
+		use sys::control::{EvThrust, EvTurn};
+		if desired.length2() < 1.0e-6 {
+			self.control.send(EvTurn(cid, 0.0));
+			self.control.send(EvThrust(cid, 0.0));
+			return;
+		}
+		let goal = desired.normalize();
+		// the cross product's sign tells us which way to rotate: a positive one
+		// puts the goal to the left, which is a turn of -1 (as for TURN_LEFT)
+		let cross = dir.x * goal.y - dir.y * goal.x;
+		let turn = if cross > 0.01 { -1.0 } else if cross < -0.01 { 1.0 } else { 0.0 };
+		self.control.send(EvTurn(cid, turn));
+		self.control.send(EvThrust(cid, if dir.dot(&goal) > ALIGN { 1.0 } else { 0.0 }));
+	}
+}
+
+/// A deterministic pseudo-random jitter in `[-WANDER_JITTER, WANDER_JITTER]`
+/// derived solely from the persistent heading, so `Wander` stays replayable
+/// under rollback with no RNG state outside the snapshot.
+fn jitter(heading: f32) -> f32 {
+	let hash = (heading * 12.9898).sin() * 43758.547;
+	(hash - hash.floor()) * 2.0 * WANDER_JITTER - WANDER_JITTER
+}
+
+impl world::System for System {
+	fn process(&mut self, _param: world::Params, data: &mut world::Components,
+			   entities: &mut Vec<world::Entity>) {
+		for ent in entities.iter() {
+			let (did, cid, sid, iid) = match (ent.directive, ent.control, ent.space, ent.inertia) {
+				(Some(did), Some(cid), Some(sid), Some(iid)) => (did, cid, sid, iid),
+				_ => continue,
+			};
+			let space = *data.space.get(sid);
+			let velocity = data.inertia.get(iid).velocity;
+			let dir = space.get_direction();
+			match *data.directive.get(did) {
+				world::Seek(target) =>
+					self.steer(cid, dir, target.sub_p(&space.pos)),
+				world::Flee(target) =>
+					self.steer(cid, dir, space.pos.sub_p(&target)),
+				world::Pursue(prey) => {
+					// the prey may have been despawned or lack kinematics
+					match (prey.space, prey.inertia) {
+						(Some(psid), Some(piid)) => {
+							let ps = *data.space.get(psid);
+							let pv = data.inertia.get(piid).velocity;
+							let offset = ps.pos.sub_p(&space.pos);
+							if offset.length() < FLEE_RADIUS {
+								// too close for comfort -- bail out
+								*data.directive.get_mut(did) = world::Flee(ps.pos);
+							} else {
+								// aim where the prey will be, scaled by time-to-intercept
+								let tti = offset.length() / velocity.length().max(1.0);
+								let lead = ps.pos.add_v(&pv.mul_s(tti));
+								self.steer(cid, dir, lead.sub_p(&space.pos));
+							}
+						},
+						_ => self.steer(cid, dir, Vector2::zero()),
+					}
+				},
+				world::Wander(heading) => {
+					let next = Rad { s: heading.s + jitter(heading.s) };
+					*data.directive.get_mut(did) = world::Wander(next);
+					self.steer(cid, dir, Vector2::new(-next.s.sin(), next.s.cos()));
+				},
+			}
+		}
+	}
+}
@@ -0,0 +1,115 @@
+use cgmath::{Rad, Point, Vector};
+use scenegraph::ces;
+use sys;
+use world;
+
+/// Muzzle speed of a freshly fired bullet, added on top of the ship velocity.
+static MUZZLE_SPEED: f32 = 5.0;
+/// Life span of a bullet in seconds.
+static LIFE_TIME: f32 = 1.0;
+/// Minimum delay between two shots while Shoot is held.
+static FIRE_PERIOD: f32 = 0.25;
+
+pub enum Event {
+	EvShoot(bool),
+}
+
+pub struct System {
+	input: Receiver<Event>,
+	ship_space: ces::Id<world::Spatial>,
+	ship_inertia: ces::Id<world::Inertial>,
+	ship_control: ces::Id<world::Control>,
+	draw: world::Drawable,
+	shooting: bool,
+}
+
+impl System {
+	pub fn new(chan: Receiver<Event>, ship_space: ces::Id<world::Spatial>,
+			   ship_inertia: ces::Id<world::Inertial>, ship_control: ces::Id<world::Control>,
+			   draw: world::Drawable) -> System {
+		System {
+			input: chan,
+			ship_space: ship_space,
+			ship_inertia: ship_inertia,
+			ship_control: ship_control,
+			draw: draw,
+			shooting: false,
+		}
+	}
+
+	fn spawn(&self, data: &mut world::Components, entities: &mut Vec<world::Entity>) {
+		let space = *data.space.get(self.ship_space);
+		let velocity = data.inertia.get(self.ship_inertia).velocity
+			+ space.get_direction().mul_s(MUZZLE_SPEED);
+		let ent = data.add()
+			.draw(self.draw.clone())
+			.space(world::Spatial {
+				pos: space.pos,
+				orient: space.orient,
+				scale: space.scale,
+			})
+			.inertia(world::Inertial {
+				velocity: velocity,
+				angular_velocity: Rad{ s: 0.0 },
+			})
+			.bullet(world::Bullet {
+				life_time: Some(LIFE_TIME),
+			})
+			.collide(world::Collidable {
+				radius: 0.05,
+				group: sys::collision::BULLET,
+				mask: sys::collision::SHIP,
+			})
+			.entity;
+		entities.push(ent);
+	}
+}
+
+impl world::System for System {
+	fn process(&mut self, param: world::Params, data: &mut world::Components,
+			   entities: &mut Vec<world::Entity>) {
+		let &(delta, _) = &*param;
+		loop {
+			match self.input.try_recv() {
+				Ok(EvShoot(value)) => self.shooting = value,
+				Err(_) => break,
+			}
+		}
+		// The fire rate lives in the ship's Control so it survives a rollback
+		// snapshot; only fire once the cooldown has elapsed.
+		let fire = {
+			let ctrl = data.control.get_mut(self.ship_control);
+			if ctrl.fire_cooldown > 0.0 {
+				ctrl.fire_cooldown -= delta;
+			}
+			if self.shooting && ctrl.fire_cooldown <= 0.0 {
+				ctrl.fire_cooldown = FIRE_PERIOD;
+				true
+			} else {
+				false
+			}
+		};
+		if fire {
+			self.spawn(data, entities);
+		}
+		// integrate life time and reap expired bullets
+		let mut dead = Vec::new();
+		for (index, ent) in entities.iter().enumerate() {
+			let bid = match ent.bullet {
+				Some(bid) => bid,
+				None => continue,
+			};
+			let expired = match data.bullet.get_mut(bid).life_time {
+				Some(ref mut t) => { *t -= delta; *t <= 0.0 },
+				None => false,
+			};
+			if expired {
+				dead.push(index);
+			}
+		}
+		for &index in dead.iter().rev() {
+			let ent = entities.remove(index).unwrap();
+			data.free(&ent);
+		}
+	}
+}
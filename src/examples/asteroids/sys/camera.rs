@@ -0,0 +1,69 @@
+use cgmath::{Point, Vector};
+use scenegraph::ces;
+use world;
+
+/// How fast a wheel notch changes the zoom factor.
+static ZOOM_SPEED: f32 = 0.1;
+
+pub enum Event {
+	EvZoom(f32),
+}
+
+pub struct System {
+	input: Receiver<Event>,
+	/// Entity carrying the active `Camera` component.
+	camera: ces::Id<world::Camera>,
+	/// Optional entity whose `Spatial` the camera tracks.
+	target: Option<ces::Id<world::Spatial>>,
+	aspect: f32,
+}
+
+impl System {
+	pub fn new(chan: Receiver<Event>, camera: ces::Id<world::Camera>,
+			   target: Option<ces::Id<world::Spatial>>, aspect: f32) -> System {
+		System {
+			input: chan,
+			camera: camera,
+			target: target,
+			aspect: aspect,
+		}
+	}
+}
+
+impl world::System for System {
+	fn process(&mut self, _param: world::Params, data: &mut world::Components,
+			   entities: &mut Vec<world::Entity>) {
+		// fold the pending wheel events into the zoom factor
+		let mut zoom = data.camera.get(self.camera).zoom;
+		loop {
+			match self.input.try_recv() {
+				Ok(EvZoom(delta)) => zoom *= 1.0 + delta * ZOOM_SPEED,
+				Err(_) => break,
+			}
+		}
+		// follow the target, if any
+		let pos = match self.target {
+			Some(sid) => data.space.get(sid).pos,
+			None => data.camera.get(self.camera).pos,
+		};
+		{
+			let cam = data.camera.get_mut(self.camera);
+			cam.pos = pos;
+			cam.zoom = zoom;
+		}
+		// x keeps the zoom and y is scaled by the aspect ratio, so world units
+		// stay square on screen
+		let screen_scale = [zoom, zoom * self.aspect, 0.0, 0.0];
+		for ent in entities.iter() {
+			let (did, sid) = match (ent.draw, ent.space) {
+				(Some(did), Some(sid)) => (did, sid),
+				_ => continue,
+			};
+			let space = *data.space.get(sid);
+			let offset = space.pos.sub_p(&pos);
+			let draw = data.draw.get_mut(did);
+			draw.program.transform = [offset.x, offset.y, space.orient.s, space.scale];
+			draw.program.screen_scale = screen_scale;
+		}
+	}
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use cgmath::Point;
+use world;
+
+/// Width of a broadphase grid cell in world units. Entities only ever test
+/// against neighbours in the same or adjacent cells.
+static CELL_SIZE: f32 = 1.0;
+
+/// Collision groups, used to fill in `Collidable::group`/`mask`.
+pub static SHIP: u32   = 1 << 0;
+pub static BULLET: u32 = 1 << 1;
+
+/// Reported when two entities whose group/mask filters agree overlap.
+pub struct Event {
+	pub target: world::Entity,
+	pub source: world::Entity,
+}
+
+pub struct System {
+	hits: Sender<Event>,
+}
+
+impl System {
+	pub fn new(hits: Sender<Event>) -> System {
+		System {
+			hits: hits,
+		}
+	}
+}
+
+/// `a` wants to collide with `b` and vice versa.
+fn filtered(a: &world::Collidable, b: &world::Collidable) -> bool {
+	a.mask & b.group != 0 && b.mask & a.group != 0
+}
+
+impl world::System for System {
+	fn process(&mut self, _param: world::Params, data: &mut world::Components,
+			   entities: &mut Vec<world::Entity>) {
+		// broadphase: bucket every collidable entity into a uniform grid
+		let mut grid: HashMap<(int, int), Vec<uint>> = HashMap::new();
+		for (index, ent) in entities.iter().enumerate() {
+			match (ent.space, ent.collide) {
+				(Some(sid), Some(_)) => {
+					let pos = data.space.get(sid).pos;
+					let cell = ((pos.x / CELL_SIZE).floor() as int,
+								(pos.y / CELL_SIZE).floor() as int);
+					grid.find_or_insert(cell, Vec::new()).push(index);
+				},
+				_ => (),
+			}
+		}
+		// candidate pairs: unique ordered pairs within each 3x3 cell neighbourhood
+		let mut pairs: Vec<(uint, uint)> = Vec::new();
+		for (&(cx, cy), bucket) in grid.iter() {
+			for dy in range(-1i, 2) {
+				for dx in range(-1i, 2) {
+					let other = match grid.find(&(cx + dx, cy + dy)) {
+						Some(o) => o,
+						None => continue,
+					};
+					for &i in bucket.iter() {
+						for &j in other.iter() {
+							let pair = if i < j { (i, j) } else { (j, i) };
+							if pair.0 != pair.1 && !pairs.contains(&pair) {
+								pairs.push(pair);
+							}
+						}
+					}
+				}
+			}
+		}
+		// circle-vs-circle, filtered by group/mask
+		let mut despawn = Vec::new();
+		for &(i, j) in pairs.iter() {
+			let (ea, eb) = (entities[i].clone(), entities[j].clone());
+			let (ca, cb) = (*data.collide.get(ea.collide.unwrap()),
+							*data.collide.get(eb.collide.unwrap()));
+			if !filtered(&ca, &cb) {
+				continue;
+			}
+			let pa = data.space.get(ea.space.unwrap()).pos;
+			let pb = data.space.get(eb.space.unwrap()).pos;
+			let limit = ca.radius + cb.radius;
+			if pa.sub_p(&pb).length2() > limit * limit {
+				continue;
+			}
+			// role is decided by group, not grid order: the bullet is the
+			// source of the hit, the ship it strikes is the target
+			let (source, target) = if ca.group & BULLET != 0 {
+				(ea, eb)
+			} else {
+				(eb, ea)
+			};
+			self.hits.send(Event { target: target, source: source });
+			// a bullet that lands is consumed by the impact
+			if ea.bullet.is_some() {
+				despawn.push(i);
+			}
+			if eb.bullet.is_some() {
+				despawn.push(j);
+			}
+		}
+		despawn.sort();
+		despawn.dedup();
+		for &index in despawn.iter().rev() {
+			let ent = entities.remove(index).unwrap();
+			data.free(&ent);
+		}
+	}
+}
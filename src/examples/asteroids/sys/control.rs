@@ -0,0 +1,54 @@
+use cgmath::{Rad, Vector};
+use scenegraph::ces;
+use world;
+
+/// Throttle used in place of normal thrust for every frame a boost is held.
+static BOOST: f32 = 3.0;
+
+pub enum Event {
+	EvThrust(ces::Id<world::Control>, f32),
+	EvTurn(ces::Id<world::Control>, f32),
+	EvBoost(ces::Id<world::Control>),
+}
+
+pub struct System {
+	input: Receiver<Event>,
+}
+
+impl System {
+	pub fn new(chan: Receiver<Event>) -> System {
+		System {
+			input: chan,
+		}
+	}
+}
+
+impl world::System for System {
+	fn process(&mut self, param: world::Params, data: &mut world::Components,
+			   entities: &mut Vec<world::Entity>) {
+		let &(delta, _) = &*param;
+		// Each event names the ship it drives, so player, peer and AI write
+		// only their own throttle and turn; nothing carries across a rollback
+		// snapshot because the fields live on the Control component.
+		loop {
+			match self.input.try_recv() {
+				Ok(EvThrust(id, v)) => data.control.get_mut(id).thrust = v,
+				Ok(EvTurn(id, v)) => data.control.get_mut(id).turn = v,
+				Ok(EvBoost(id)) => data.control.get_mut(id).thrust = BOOST,
+				Err(_) => break,
+			}
+		}
+		for ent in entities.iter() {
+			let (cid, sid, iid) = match (ent.control, ent.space, ent.inertia) {
+				(Some(c), Some(s), Some(i)) => (c, s, i),
+				_ => continue,
+			};
+			let ctrl = *data.control.get(cid);
+			let dir = data.space.get(sid).get_direction();
+			let inertia = data.inertia.get_mut(iid);
+			inertia.velocity = inertia.velocity
+				+ dir.mul_s(ctrl.thrust * ctrl.thrust_speed * delta);
+			inertia.angular_velocity = Rad { s: ctrl.turn * ctrl.turn_speed };
+		}
+	}
+}
@@ -0,0 +1,182 @@
+use gfx;
+use scenegraph::ces;
+use sys;
+use world;
+
+/// The simulation advances in fixed increments so that two peers stepping the
+/// same inputs reach bit-identical states. Never derive this from wall-clock
+/// time -- all systems must be pure functions of (state, inputs, TIMESTEP).
+pub static TIMESTEP: world::Delta = 1.0 / 60.0;
+/// Number of past frames we keep around to roll back to.
+static HISTORY: uint = 64;
+
+/// Packed per-frame input, one bit per action. Cheap to clone and to ship
+/// across the wire as the payload of a `Packet`.
+pub type Input = u32;
+pub static THRUST: Input     = 1 << 0;
+pub static TURN_LEFT: Input  = 1 << 1;
+pub static TURN_RIGHT: Input = 1 << 2;
+pub static SHOOT: Input      = 1 << 3;
+pub static BOOST: Input      = 1 << 4;
+
+/// The minimal thing exchanged with the peer every frame.
+pub struct Packet {
+	pub frame: u64,
+	pub input: Input,
+}
+
+struct Frame {
+	snapshot: world::Snapshot,
+	local: Input,
+	remote: Input,
+	/// `true` once the authoritative remote input has arrived.
+	confirmed: bool,
+}
+
+/// Drives the `World` in lockstep with a remote peer, rolling back and
+/// re-simulating whenever a prediction turns out wrong.
+pub struct Session {
+	to_peer: Sender<Packet>,
+	from_peer: Receiver<Packet>,
+	control: Sender<sys::control::Event>,
+	bullet: Sender<sys::bullet::Event>,
+	/// Control component of the locally driven ship.
+	local_ship: ces::Id<world::Control>,
+	/// Control component of the ship the remote peer drives.
+	remote_ship: ces::Id<world::Control>,
+	history: Vec<Frame>,
+	frame: u64,
+	/// Last remote input we saw, repeated while the peer is silent.
+	predicted: Input,
+}
+
+impl Session {
+	pub fn new(to_peer: Sender<Packet>, from_peer: Receiver<Packet>,
+			   control: Sender<sys::control::Event>,
+			   bullet: Sender<sys::bullet::Event>,
+			   local_ship: ces::Id<world::Control>,
+			   remote_ship: ces::Id<world::Control>) -> Session {
+		Session {
+			to_peer: to_peer,
+			from_peer: from_peer,
+			control: control,
+			bullet: bullet,
+			local_ship: local_ship,
+			remote_ship: remote_ship,
+			history: Vec::with_capacity(HISTORY),
+			frame: 0,
+			predicted: 0,
+		}
+	}
+
+	/// Translate a frame's input bitset into the control events that steer the
+	/// ship named by `ship`. Driving is symmetric for both peers, so the local
+	/// and remote ships go through the very same path and a replayed frame is
+	/// fed exactly like a live one.
+	fn feed(&self, ship: ces::Id<world::Control>, input: Input) {
+		use sys::control::{EvThrust, EvTurn, EvBoost};
+		if input & BOOST != 0 {
+			self.control.send(EvBoost(ship));
+		} else {
+			self.control.send(EvThrust(ship, if input & THRUST != 0 { 1.0 } else { 0.0 }));
+		}
+		self.control.send(EvTurn(ship, if input & TURN_LEFT != 0 { -1.0 }
+			else if input & TURN_RIGHT != 0 { 1.0 } else { 0.0 }));
+	}
+
+	/// Feed both ships' inputs for one frame and advance the world one fixed
+	/// tick. During replay the draw output lands in a throwaway list; only the
+	/// live tick passes the real `list`. Firing is wired to the local ship's
+	/// bullet system, so only the local input shoots here.
+	fn step(&self, world: &mut world::World, local: Input, remote: Input,
+			list: &mut gfx::DrawList) {
+		use sys::bullet::{EvShoot};
+		self.feed(self.local_ship, local);
+		self.feed(self.remote_ship, remote);
+		self.bullet.send(EvShoot(local & SHOOT != 0));
+		world.update(&mut (TIMESTEP, list));
+	}
+
+	fn index(&self, frame: u64) -> Option<uint> {
+		// the buffer holds a contiguous run ending just before `self.frame`,
+		// so frame N sits at offset N - base
+		let base = self.frame - self.history.len() as u64;
+		if frame >= base && (frame - base) as uint < self.history.len() {
+			Some((frame - base) as uint)
+		} else {
+			None
+		}
+	}
+
+	/// Feed the local player's actions, exchange with the peer, and step the
+	/// world forward exactly one fixed tick -- rolling back first if a past
+	/// prediction has just been contradicted.
+	pub fn advance(&mut self, world: &mut world::World, local: Input,
+				   list: &mut gfx::DrawList) {
+		// take delivery of any authoritative remote inputs
+		let mut earliest_fix = None;
+		loop {
+			match self.from_peer.try_recv() {
+				Ok(pkt) => match self.index(pkt.frame) {
+					Some(i) => {
+						let wrong = !self.history[i].confirmed
+							&& self.history[i].remote != pkt.input;
+						self.history[i].remote = pkt.input;
+						self.history[i].confirmed = true;
+						self.predicted = pkt.input;
+						if wrong {
+							earliest_fix = Some(match earliest_fix {
+								Some(e) if e <= pkt.frame => e,
+								_ => pkt.frame,
+							});
+						}
+					},
+					None => (),
+				},
+				Err(_) => break,
+			}
+		}
+		// a contradicted prediction means rewind and replay
+		match earliest_fix {
+			Some(f) => self.resimulate(world, f),
+			None => (),
+		}
+		// record the snapshot that opens this frame, then simulate it
+		self.push(Frame {
+			snapshot: world.save_state(),
+			local: local,
+			remote: self.predicted,
+			confirmed: false,
+		});
+		self.to_peer.send(Packet { frame: self.frame, input: local });
+		self.step(world, local, self.predicted, list);
+		self.frame += 1;
+	}
+
+	fn push(&mut self, frame: Frame) {
+		if self.history.len() == HISTORY {
+			self.history.remove(0);
+		}
+		self.history.push(frame);
+	}
+
+	/// Restore the snapshot taken at `frame` and replay every frame since,
+	/// using the now-known inputs, up to the live frame.
+	fn resimulate(&mut self, world: &mut world::World, frame: u64) {
+		let start = match self.index(frame) {
+			Some(i) => i,
+			None => return, // fell off the end of the ring; nothing we can do
+		};
+		world.load_state(self.history[start].snapshot.clone());
+		let mut scratch = gfx::DrawList::new();
+		for i in range(start, self.history.len()) {
+			let (local, remote) = (self.history[i].local, self.history[i].remote);
+			// refresh the stored snapshot so a later rollback starts from the
+			// corrected state rather than the mispredicted one
+			self.history[i].snapshot = world.save_state();
+			// replay both ships with the now-known inputs, re-applying the
+			// corrected remote input that triggered this rollback
+			self.step(world, local, remote, &mut scratch);
+		}
+	}
+}
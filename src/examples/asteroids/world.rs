@@ -49,6 +49,12 @@ pub struct Inertial {
 pub struct Control {
 	pub thrust_speed: f32,
 	pub turn_speed: f32,
+	/// Seconds left before this ship may fire again.
+	pub fire_cooldown: f32,
+	/// Current throttle and turn for *this* ship, written by whoever drives it
+	/// (the player, a peer, or the AI) so ships steer independently.
+	pub thrust: f32,
+	pub turn: f32,
 }
 
 #[deriving(Clone)]
@@ -56,6 +62,34 @@ pub struct Bullet {
 	pub life_time: Option<f32>,
 }
 
+#[deriving(Clone)]
+pub struct Camera {
+	pub pos: Point2<f32>,
+	pub zoom: f32,
+}
+
+#[deriving(Clone)]
+pub struct Collidable {
+	pub radius: f32,
+	/// Bit set describing what this entity *is*.
+	pub group: u32,
+	/// Bit set describing which groups this entity collides *with*.
+	pub mask: u32,
+}
+
+/// A steering goal for an AI-controlled ship, swappable at runtime.
+#[deriving(Clone)]
+pub enum Directive {
+	/// Steer towards a fixed point.
+	Seek(Point2<f32>),
+	/// Steer away from a fixed point.
+	Flee(Point2<f32>),
+	/// Chase another entity, aiming at its predicted position.
+	Pursue(Entity),
+	/// Drift, slowly perturbing the persistent heading.
+	Wander(Rad<f32>),
+}
+
 
 world! { ces (Params),
 	draw: Drawable,
@@ -63,4 +97,46 @@ world! { ces (Params),
 	inertia: Inertial,
 	control: Control,
 	bullet: Bullet,
+	camera: Camera,
+	collide: Collidable,
+	directive: Directive,
+}
+
+/// A deep copy of the whole simulation state, used by the rollback netcode
+/// to rewind to an earlier frame and re-simulate deterministically.
+#[deriving(Clone)]
+pub struct Snapshot {
+	pub data: Components,
+	pub entities: Vec<Entity>,
+}
+
+impl Components {
+	/// Free every component slot referenced by `entity`. Callers remove the
+	/// entity from the entity table themselves.
+	pub fn free(&mut self, entity: &Entity) {
+		entity.draw.map(|id| self.draw.remove(id));
+		entity.space.map(|id| self.space.remove(id));
+		entity.inertia.map(|id| self.inertia.remove(id));
+		entity.control.map(|id| self.control.remove(id));
+		entity.bullet.map(|id| self.bullet.remove(id));
+		entity.camera.map(|id| self.camera.remove(id));
+		entity.collide.map(|id| self.collide.remove(id));
+		entity.directive.map(|id| self.directive.remove(id));
+	}
+}
+
+impl World {
+	/// Clone every component store plus the entity table into a snapshot.
+	pub fn save_state(&self) -> Snapshot {
+		Snapshot {
+			data: self.data.clone(),
+			entities: self.entities.clone(),
+		}
+	}
+
+	/// Overwrite the live state with a previously saved snapshot.
+	pub fn load_state(&mut self, snap: Snapshot) {
+		self.data = snap.data;
+		self.entities = snap.entities;
+	}
 }